@@ -0,0 +1,82 @@
+//! Syscall dispatch: decode `(syscall_id, args)` from a trap, enforce any
+//! seccomp filter, count the call in `Info::syscall_times`, and call the
+//! matching `sys_*` function.
+
+mod process;
+mod ptrace;
+mod seccomp;
+mod thread;
+
+use process::*;
+use ptrace::sys_ptrace;
+use seccomp::sys_seccomp;
+use thread::{sys_gettid, sys_thread_create, sys_waittid};
+
+const SYSCALL_PTRACE: usize = 117;
+const SYSCALL_SCHED_SETSCHEDULER: usize = 119;
+const SYSCALL_SCHED_GETSCHEDULER: usize = 120;
+const SYSCALL_SCHED_GET_PRIORITY_MAX: usize = 121;
+const SYSCALL_SCHED_GET_PRIORITY_MIN: usize = 122;
+const SYSCALL_SET_PRIORITY: usize = 140;
+const SYSCALL_GETRLIMIT: usize = 163;
+const SYSCALL_SETRLIMIT: usize = 164;
+const SYSCALL_GETRUSAGE: usize = 165;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_MUNMAP: usize = 215;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_SECCOMP: usize = 277;
+const SYSCALL_SPAWN: usize = 400;
+const SYSCALL_CLONE: usize = 401;
+const SYSCALL_TASK_INFO: usize = 410;
+const SYSCALL_EXIT: usize = 93;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_THREAD_CREATE: usize = 1000;
+const SYSCALL_GETTID: usize = 1001;
+const SYSCALL_WAITTID: usize = 1002;
+
+/// Dispatch one syscall trap. A task denied by its seccomp filter is
+/// killed before the call is counted or run at all.
+pub fn syscall(syscall_id: usize, args: [usize; 4]) -> isize {
+    let task = crate::task::current_task().unwrap();
+    let pid = task.getpid();
+    if !crate::task::seccomp::is_allowed(pid, syscall_id) {
+        drop(task);
+        crate::task::exit_current_and_run_next(crate::task::seccomp::SECCOMP_KILL_EXIT_CODE);
+        panic!("Unreachable: task killed by seccomp");
+    }
+    task.inner_exclusive_access().info.record_syscall(syscall_id);
+    drop(task);
+
+    match syscall_id {
+        SYSCALL_EXIT => sys_exit(args[0] as i32),
+        SYSCALL_YIELD => sys_yield(),
+        SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_FORK => sys_fork(),
+        SYSCALL_EXEC => sys_exec(args[0] as *const u8),
+        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
+        SYSCALL_GET_TIME => sys_get_time(args[0] as *mut TimeVal, args[1]),
+        SYSCALL_TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
+        SYSCALL_SET_PRIORITY => sys_set_priority(args[0] as isize),
+        SYSCALL_MMAP => sys_mmap(args[0], args[1], args[2]),
+        SYSCALL_MUNMAP => sys_munmap(args[0], args[1]),
+        SYSCALL_SPAWN => sys_spawn(args[0] as *const u8),
+        SYSCALL_CLONE => sys_clone(args[0], args[1]),
+        SYSCALL_GETRUSAGE => sys_getrusage(args[0] as isize, args[1] as *mut RUsage),
+        SYSCALL_GETRLIMIT => sys_getrlimit(args[0], args[1] as *mut crate::task::info::RLimit64),
+        SYSCALL_SETRLIMIT => sys_setrlimit(args[0], args[1] as *const crate::task::info::RLimit64),
+        SYSCALL_SCHED_SETSCHEDULER => sys_sched_setscheduler(args[0], args[1], args[2]),
+        SYSCALL_SCHED_GETSCHEDULER => sys_sched_getscheduler(args[0]),
+        SYSCALL_SCHED_GET_PRIORITY_MAX => sys_sched_get_priority_max(args[0]),
+        SYSCALL_SCHED_GET_PRIORITY_MIN => sys_sched_get_priority_min(args[0]),
+        SYSCALL_PTRACE => sys_ptrace(args[0], args[1], args[2], args[3]),
+        SYSCALL_SECCOMP => sys_seccomp(args[0], args[1] as *const u8),
+        SYSCALL_THREAD_CREATE => sys_thread_create(args[0], args[1]),
+        SYSCALL_GETTID => sys_gettid(),
+        SYSCALL_WAITTID => sys_waittid(args[0]),
+        _ => panic!("Unsupported syscall_id: {}", syscall_id),
+    }
+}
@@ -3,9 +3,24 @@
 use crate::loader::get_app_data_by_name;
 use crate::mm::{MapPermission, translated_refmut, translated_str, VirtAddr};
 use crate::task::{add_task, current_task, current_user_token, exit_current_and_run_next, suspend_current_and_run_next, TaskControlBlock, TaskStatus};
+use crate::task::info::{RLimit64, SchedPolicy, RLIMIT_AS, RLIM_INFINITY};
+use crate::task::vfork::{vfork_is_done, vfork_wait_begin, vfork_wait_end};
 use crate::timer::{get_time_us};
 use alloc::sync::Arc;
-use crate::config::MAX_SYSCALL_NUM;
+use bitflags::bitflags;
+use crate::config::{MAX_SYSCALL_NUM, PAGE_SIZE};
+
+bitflags! {
+    /// Flags accepted by [`sys_clone`], mirroring the subset of Linux's
+    /// `clone(2)` flags this kernel understands.
+    pub struct CloneFlags: usize {
+        /// Share the caller's `memory_set` (address space) instead of
+        /// copying it on creation.
+        const CLONE_VM = 0x0000_0100;
+        /// Suspend the caller until the child execs or exits.
+        const CLONE_VFORK = 0x0000_4000;
+    }
+}
 
 #[repr(C)]
 #[derive(Debug)]
@@ -48,11 +63,50 @@ pub fn sys_fork() -> isize {
     // we do not have to move to next instruction since we have done it before
     // for child process, fork returns 0
     trap_cx.x[10] = 0;
+    crate::task::seccomp::inherit(current_task.getpid(), new_pid);
     // add new task to scheduler
     add_task(new_task);
     new_pid as isize
 }
 
+/// Syscall Clone, generalizing `fork`/`vfork`/thread-creation behind
+/// `CloneFlags`. With `CLONE_VM` the child shares the caller's `memory_set`
+/// (see `TaskControlBlock::fork_shared_vm`) instead of copying it; without
+/// it, this falls back to the existing copy-on-fork path. `CLONE_VFORK`
+/// additionally blocks the caller until the child execs or exits.
+pub fn sys_clone(flags: usize, stack: usize) -> isize {
+    let flags = CloneFlags::from_bits_truncate(flags);
+    let current_task = current_task().unwrap();
+    let new_task = if flags.contains(CloneFlags::CLONE_VM) {
+        current_task.fork_shared_vm()
+    } else {
+        current_task.fork()
+    };
+    let new_pid = new_task.pid.0;
+
+    let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
+    if stack != 0 {
+        trap_cx.x[2] = stack;
+    }
+    // for child process, clone returns 0
+    trap_cx.x[10] = 0;
+    crate::task::seccomp::inherit(current_task.getpid(), new_pid);
+
+    if flags.contains(CloneFlags::CLONE_VFORK) {
+        vfork_wait_begin(new_pid);
+    }
+    add_task(new_task.clone());
+
+    if flags.contains(CloneFlags::CLONE_VFORK) {
+        while !new_task.inner_exclusive_access().is_zombie() && !vfork_is_done(new_pid) {
+            suspend_current_and_run_next();
+        }
+        vfork_wait_end(new_pid);
+    }
+
+    new_pid as isize
+}
+
 /// Syscall Exec which accepts the elf path
 pub fn sys_exec(path: *const u8) -> isize {
     let token = current_user_token();
@@ -61,6 +115,17 @@ pub fn sys_exec(path: *const u8) -> isize {
         let task = current_task().unwrap();
         task.exec(data);
         task.record_start();
+        let pid = task.getpid();
+        crate::task::vfork::vfork_mark_done(pid);
+        if crate::task::ptrace::wants_traceme(pid) {
+            let parent = task.inner_exclusive_access().parent.as_ref().and_then(|p| p.upgrade());
+            if let Some(parent) = parent {
+                crate::task::ptrace::attach(&parent, pid);
+                while crate::task::ptrace::is_stopped(pid) {
+                    suspend_current_and_run_next();
+                }
+            }
+        }
         0
     } else {
         -1
@@ -155,7 +220,12 @@ pub fn sys_mmap(start: usize, len: usize, port: usize) -> isize {
 
     let task = current_task().unwrap();
     let mut task_cx = task.inner_exclusive_access();
+    let limit = task_cx.limits.exclusive_access().rlimit_as.cur;
+    if limit != RLIM_INFINITY && (task_cx.limits.exclusive_access().mapped_bytes + len) as u64 > limit {
+        return -1;
+    }
     if let Some(()) = task_cx.memory_set.insert_framed_area_check(start_va, end_va, perm.unwrap()){
+        task_cx.limits.exclusive_access().mapped_bytes += len;
         0
     }else {
         -1
@@ -171,6 +241,8 @@ pub fn sys_munmap(start: usize, len: usize) -> isize {
     let task = current_task().unwrap();
     let mut task_cx = task.inner_exclusive_access();
     if let Some(()) = task_cx.memory_set.move_frame_area_check(start_va, end_va) {
+        let mut limits = task_cx.limits.exclusive_access();
+        limits.mapped_bytes = limits.mapped_bytes.saturating_sub(len);
         0
     }else {
         -1
@@ -200,6 +272,13 @@ pub fn sys_spawn(path: *const u8) -> isize {
     drop(task_cx_child);
     let mut task_cx_parent = current_task.inner_exclusive_access();
     task_cx_parent.children.push(new_task.clone());
+    drop(task_cx_parent);
+
+    // Unlike fork/clone, spawn builds the child via TaskControlBlock::new
+    // rather than deriving it from the parent, so its seccomp filter (kept
+    // in a side table keyed by pid, not copied along with anything on
+    // TaskControlBlock) needs to be inherited explicitly here too.
+    crate::task::seccomp::inherit(current_task.getpid(), pid);
 
     add_task(new_task);
 
@@ -207,6 +286,137 @@ pub fn sys_spawn(path: *const u8) -> isize {
     pid as isize
 }
 
+/// `who` value `sys_getrusage` accepts, matching Linux's `RUSAGE_SELF`.
+pub const RUSAGE_SELF: isize = 0;
+
+/// A (partial) POSIX `rusage`: accumulated CPU time and total syscalls,
+/// taken from `Info`, and a memory figure taken from the address space's
+/// `AddressSpaceLimits`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct RUsage {
+    pub ru_utime: TimeVal,
+    pub ru_syscalls: usize,
+    /// Pages mapped through `sys_mmap` in this address space (net of
+    /// `sys_munmap`). ELF segments, the user stack and the trap-context
+    /// page are not counted.
+    pub ru_maxrss: usize,
+}
+
+/// Fill in `usage` for `who` (only `RUSAGE_SELF` is supported).
+pub fn sys_getrusage(who: isize, usage: *mut RUsage) -> isize {
+    if who != RUSAGE_SELF {
+        return -1;
+    }
+    let current_task = current_task().unwrap();
+    let current_cx = current_task.inner_exclusive_access();
+    let usage = translated_refmut(current_cx.memory_set.token(), usage);
+    let during_ms = current_cx.info.during_time();
+    usage.ru_utime.sec = during_ms / 1000;
+    usage.ru_utime.usec = (during_ms % 1000) * 1000;
+    usage.ru_syscalls = current_cx.info.syscall_times.iter().sum::<u32>() as usize;
+    usage.ru_maxrss = current_cx.limits.exclusive_access().mapped_bytes / PAGE_SIZE;
+    0
+}
+
+/// Read the current task's limit for `resource` (only `RLIMIT_AS` is
+/// supported) into `*rlim`.
+pub fn sys_getrlimit(resource: usize, rlim: *mut RLimit64) -> isize {
+    if resource != RLIMIT_AS {
+        return -1;
+    }
+    let current_task = current_task().unwrap();
+    let current_cx = current_task.inner_exclusive_access();
+    *translated_refmut(current_cx.memory_set.token(), rlim) = current_cx.limits.exclusive_access().rlimit_as;
+    0
+}
+
+/// Set the current task's limit for `resource` (only `RLIMIT_AS` is
+/// supported) from `*rlim`.
+pub fn sys_setrlimit(resource: usize, rlim: *const RLimit64) -> isize {
+    if resource != RLIMIT_AS {
+        return -1;
+    }
+    let current_task = current_task().unwrap();
+    let current_cx = current_task.inner_exclusive_access();
+    let token = current_cx.memory_set.token();
+    let new_limit = *translated_refmut(token, rlim as *mut RLimit64);
+    current_cx.limits.exclusive_access().rlimit_as = new_limit;
+    0
+}
+
+/// `policy` values accepted by `sys_sched_setscheduler`/`sys_sched_getscheduler`.
+pub const SCHED_FIFO: usize = 1;
+pub const SCHED_RR: usize = 2;
+pub const SCHED_STRIDE: usize = 3;
+
+/// Fixed RR quantum; unlike `rt_priority`, this isn't currently settable
+/// per task.
+const RR_QUANTUM_MS: usize = 10;
+
+/// Set `pid`'s scheduling policy. Only the caller can be targeted: `pid`
+/// must be 0 or the caller's own pid, otherwise -1. `param` is the real-time priority for
+/// `SCHED_FIFO`/`SCHED_RR` and ignored for `SCHED_STRIDE`.
+pub fn sys_sched_setscheduler(pid: usize, policy: usize, param: usize) -> isize {
+    let current_task = current_task().unwrap();
+    if pid != 0 && pid != current_task.getpid() {
+        return -1;
+    }
+    let mut inner = current_task.inner_exclusive_access();
+    match policy {
+        SCHED_FIFO => {
+            inner.info.policy = SchedPolicy::Fifo;
+            inner.info.rt_priority = param;
+        }
+        SCHED_RR => {
+            inner.info.policy = SchedPolicy::RoundRobin { quantum_ms: RR_QUANTUM_MS };
+            inner.info.rt_priority = param;
+            inner.info.reset_rr_quantum();
+        }
+        SCHED_STRIDE => {
+            inner.info.policy = SchedPolicy::Stride;
+        }
+        _ => return -1,
+    }
+    0
+}
+
+/// Return `pid`'s scheduling policy. Only the caller can be queried: `pid`
+/// must be 0 or the caller's own pid, otherwise -1.
+pub fn sys_sched_getscheduler(pid: usize) -> isize {
+    let current_task = current_task().unwrap();
+    if pid != 0 && pid != current_task.getpid() {
+        return -1;
+    }
+    match current_task.inner_exclusive_access().info.policy {
+        SchedPolicy::Fifo => SCHED_FIFO as isize,
+        SchedPolicy::RoundRobin { .. } => SCHED_RR as isize,
+        SchedPolicy::Stride => SCHED_STRIDE as isize,
+    }
+}
+
+/// The highest valid priority for `policy`.
+pub fn sys_sched_get_priority_max(policy: usize) -> isize {
+    match policy {
+        SCHED_FIFO | SCHED_RR => 99,
+        // Stride "priority" is the raw weight fed into `BIG_PRIORITY /
+        // priority`; it has no fixed upper bound.
+        SCHED_STRIDE => isize::MAX,
+        _ => -1,
+    }
+}
+
+/// The lowest valid priority for `policy`.
+pub fn sys_sched_get_priority_min(policy: usize) -> isize {
+    match policy {
+        SCHED_FIFO | SCHED_RR => 1,
+        // Priorities below 2 are rejected (see `sys_set_priority`), so the
+        // stride step BIG_PRIORITY / priority never exceeds BIG_PRIORITY / 2.
+        SCHED_STRIDE => 2,
+        _ => -1,
+    }
+}
+
 fn change_port_to_permission(port: usize) -> Option<MapPermission> {
     let user_permission = MapPermission::U;
     let (read, write, execute) = (MapPermission::R, MapPermission::W, MapPermission::X);
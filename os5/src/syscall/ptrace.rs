@@ -0,0 +1,96 @@
+//! `sys_ptrace`: a minimal attach/continue debugging facility built on
+//! the existing parent/child relationship (`children`, `parent`, `waitpid`).
+//! There is no single-step request.
+
+use crate::mm::translated_refmut;
+use crate::task::ptrace::{
+    self, PTRACE_ATTACH, PTRACE_CONT, PTRACE_GETREGS, PTRACE_PEEKDATA, PTRACE_POKEDATA,
+    PTRACE_SETREGS, PTRACE_TRACEME,
+};
+use crate::task::{add_task, current_task, TaskControlBlock};
+use alloc::sync::Arc;
+
+pub fn sys_ptrace(request: usize, pid: usize, addr: usize, data: usize) -> isize {
+    match request {
+        PTRACE_TRACEME => {
+            let current = current_task().unwrap();
+            ptrace::mark_traceme(current.getpid());
+            0
+        }
+        PTRACE_ATTACH => {
+            let current = current_task().unwrap();
+            let is_child = current
+                .inner_exclusive_access()
+                .children
+                .iter()
+                .any(|c| c.getpid() == pid);
+            if !is_child {
+                return -1;
+            }
+            ptrace::attach(&current, pid);
+            0
+        }
+        PTRACE_PEEKDATA => {
+            let current = current_task().unwrap();
+            let Some(tracee) = find_tracee(&current, pid) else {
+                return -1;
+            };
+            let token = tracee.inner_exclusive_access().memory_set.token();
+            *translated_refmut(token, addr as *mut usize) as isize
+        }
+        PTRACE_POKEDATA => {
+            let current = current_task().unwrap();
+            let Some(tracee) = find_tracee(&current, pid) else {
+                return -1;
+            };
+            let token = tracee.inner_exclusive_access().memory_set.token();
+            *translated_refmut(token, addr as *mut usize) = data;
+            0
+        }
+        PTRACE_GETREGS => {
+            let current = current_task().unwrap();
+            let Some(tracee) = find_tracee(&current, pid) else {
+                return -1;
+            };
+            let regs = tracee.inner_exclusive_access().get_trap_cx().x;
+            let token = current.inner_exclusive_access().memory_set.token();
+            *translated_refmut(token, data as *mut [usize; 32]) = regs;
+            0
+        }
+        PTRACE_SETREGS => {
+            let current = current_task().unwrap();
+            let Some(tracee) = find_tracee(&current, pid) else {
+                return -1;
+            };
+            let token = current.inner_exclusive_access().memory_set.token();
+            let regs = *translated_refmut(token, data as *mut [usize; 32]);
+            tracee.inner_exclusive_access().get_trap_cx().x = regs;
+            0
+        }
+        PTRACE_CONT => {
+            let current = current_task().unwrap();
+            let Some(tracee) = find_tracee(&current, pid) else {
+                return -1;
+            };
+            ptrace::cont(pid);
+            add_task(tracee);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// `pid`'s `TaskControlBlock`, but only if `tracer` is its registered
+/// ptrace tracer.
+fn find_tracee(tracer: &Arc<TaskControlBlock>, pid: usize) -> Option<Arc<TaskControlBlock>> {
+    let registered = ptrace::tracer_of(pid)?;
+    if !Arc::ptr_eq(&registered, tracer) {
+        return None;
+    }
+    tracer
+        .inner_exclusive_access()
+        .children
+        .iter()
+        .find(|c| c.getpid() == pid)
+        .cloned()
+}
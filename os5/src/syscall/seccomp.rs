@@ -0,0 +1,35 @@
+//! `sys_seccomp`: install a syscall allowlist for the current task.
+
+use crate::config::MAX_SYSCALL_NUM;
+use crate::mm::translated_refmut;
+use crate::task::current_task;
+use crate::task::seccomp::{self, SECCOMP_MODE_FILTER, SECCOMP_MODE_STRICT};
+use alloc::vec;
+
+/// `mode = SECCOMP_MODE_STRICT` permits only `read`/`write`/`exit`/`yield`.
+/// `mode = SECCOMP_MODE_FILTER` installs the `MAX_SYSCALL_NUM`-byte bitmap
+/// at `filter_ptr` (one byte per syscall id, nonzero = allowed).
+pub fn sys_seccomp(mode: usize, filter_ptr: *const u8) -> isize {
+    let current = current_task().unwrap();
+    let pid = current.getpid();
+    match mode {
+        SECCOMP_MODE_STRICT => {
+            seccomp::set_strict(pid);
+            0
+        }
+        SECCOMP_MODE_FILTER => {
+            if filter_ptr.is_null() {
+                return -1;
+            }
+            let token = current.inner_exclusive_access().memory_set.token();
+            let mut bitmap = vec![false; MAX_SYSCALL_NUM];
+            for (id, allowed) in bitmap.iter_mut().enumerate() {
+                let byte_ptr = unsafe { filter_ptr.add(id) } as *mut u8;
+                *allowed = *translated_refmut(token, byte_ptr) != 0;
+            }
+            seccomp::set_filter(pid, bitmap);
+            0
+        }
+        _ => -1,
+    }
+}
@@ -0,0 +1,29 @@
+//! Thread-related syscalls: creation, join, and tid introspection.
+
+use crate::task::{add_task, current_task};
+
+/// Create a new thread in the current task's address space, entering at
+/// `entry` with `arg` in `a0`. Returns the new thread's tid.
+pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
+    let current_task = current_task().unwrap();
+    let new_task = current_task.new_thread(entry, arg);
+    // new_thread allocates the thread its own pid under the hood (see
+    // fork_shared_vm), and seccomp filters are kept in a side table keyed
+    // by pid rather than carried on TaskControlBlock, so they need
+    // inheriting explicitly here, same as fork/clone/spawn.
+    crate::task::seccomp::inherit(current_task.getpid(), new_task.getpid());
+    let new_tid = new_task.gettid();
+    add_task(new_task);
+    new_tid as isize
+}
+
+/// Return the calling task's tid (0 for a process's main task).
+pub fn sys_gettid() -> isize {
+    current_task().unwrap().gettid() as isize
+}
+
+/// Wait for thread `tid` to exit, returning its exit code. -1 if no such
+/// thread, -2 if it is still running.
+pub fn sys_waittid(tid: usize) -> isize {
+    current_task().unwrap().wait_thread(tid)
+}
@@ -0,0 +1,94 @@
+//! The `CLONE_VM` fork variant: shares the parent's `memory_set` (by
+//! `Arc`-cloning its page table) instead of copying it, so the child runs in
+//! the same address space as the parent. This is the foundation both POSIX
+//! threads and `vfork` build on.
+
+use super::TaskControlBlock;
+use crate::config::PAGE_SIZE;
+use crate::mm::{MapPermission, VirtAddr};
+use crate::sync::UPSafeCell;
+use crate::task::context::TaskContext;
+use crate::task::pid::{pid_alloc, KernelStack};
+use crate::task::TaskControlBlockInner;
+use crate::task::TaskStatus;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// Base VA for the private trap-context page `fork_shared_vm` maps for
+/// each `CLONE_VM` child, indexed by pid. Laid out well below
+/// `id::THREAD_RES_BASE`'s per-tid region so the pid-indexed and
+/// tid-indexed ranges can never collide within one shared `memory_set`.
+const CLONE_TRAP_CX_BASE: usize = 0x1_0000_0000 - 0x1000_0000;
+
+pub(super) fn clone_trap_cx_va(pid: usize) -> usize {
+    CLONE_TRAP_CX_BASE - pid * PAGE_SIZE
+}
+
+/// Unmap the private trap-context page `fork_shared_vm` mapped for `pid`.
+/// Used by `new_thread`, which immediately supersedes it with
+/// `TaskUserRes`'s own tid-indexed trap-context page.
+pub(super) fn unmap_private_trap_cx(memory_set: &mut crate::mm::MemorySet, pid: usize) {
+    let va = clone_trap_cx_va(pid);
+    memory_set.move_frame_area_check(va.into(), (va + PAGE_SIZE).into());
+}
+
+impl TaskControlBlock {
+    /// Like `fork`, but `self` and the child share one `memory_set` instead
+    /// of the child getting a deep copy, mirroring `fork`'s structure with
+    /// the memory_set field Arc-shared rather than cloned. The child still
+    /// gets its own trap-context page mapped into that shared address
+    /// space (mirroring `TaskUserRes`'s per-thread trap-context page):
+    /// aliasing the parent's `trap_cx_ppn` would mean the next write to
+    /// the child's trap context (e.g. `sys_clone` setting `sp`/`a0`)
+    /// clobbers the parent's own, live trap context.
+    pub fn fork_shared_vm(self: &Arc<Self>) -> Arc<Self> {
+        let mut parent_inner = self.inner_exclusive_access();
+        let mut memory_set = parent_inner.memory_set.clone();
+        let pid_handle = pid_alloc();
+        let trap_cx_va = clone_trap_cx_va(pid_handle.0);
+        memory_set.insert_framed_area_check(
+            trap_cx_va.into(),
+            (trap_cx_va + PAGE_SIZE).into(),
+            MapPermission::R | MapPermission::W,
+        );
+        let trap_cx_ppn = memory_set
+            .translate(VirtAddr::from(trap_cx_va).into())
+            .unwrap()
+            .ppn();
+        // Seed the child's trap context with the parent's live register
+        // state (the same thing a deep-copying `fork` gets for free by
+        // copying the whole page).
+        trap_cx_ppn
+            .get_bytes_array()
+            .copy_from_slice(parent_inner.trap_cx_ppn.get_bytes_array());
+
+        let kernel_stack = KernelStack::new(&pid_handle);
+        let kernel_stack_top = kernel_stack.get_top();
+        let task_control_block = Arc::new(TaskControlBlock {
+            pid: pid_handle,
+            kernel_stack,
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    trap_cx_ppn,
+                    base_size: parent_inner.base_size,
+                    task_cx: TaskContext::goto_trap_return(kernel_stack_top),
+                    task_status: TaskStatus::Ready,
+                    memory_set,
+                    parent: Some(Arc::downgrade(self)),
+                    children: Vec::new(),
+                    exit_code: 0,
+                    info: parent_inner.info,
+                    task_res: None,
+                    // Sharing `memory_set` means mapping/limit accounting
+                    // must be shared too, or RLIMIT_AS could be bypassed by
+                    // having each sharer map up to the limit independently.
+                    limits: parent_inner.limits.clone(),
+                })
+            },
+        });
+        parent_inner.children.push(task_control_block.clone());
+        let trap_cx = task_control_block.inner_exclusive_access().get_trap_cx();
+        trap_cx.kernel_sp = kernel_stack_top;
+        task_control_block
+    }
+}
@@ -0,0 +1,32 @@
+//! Task context: the callee-saved registers swapped by `__switch`.
+
+use crate::trap::trap_return;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TaskContext {
+    ra: usize,
+    sp: usize,
+    s: [usize; 12],
+}
+
+impl TaskContext {
+    pub fn zero_init() -> Self {
+        Self {
+            ra: 0,
+            sp: 0,
+            s: [0; 12],
+        }
+    }
+
+    /// A context that, when switched to, returns straight to `trap_return`
+    /// running on `kstack_ptr`. Used for both freshly-forked tasks and
+    /// freshly-created threads.
+    pub fn goto_trap_return(kstack_ptr: usize) -> Self {
+        Self {
+            ra: trap_return as usize,
+            sp: kstack_ptr,
+            s: [0; 12],
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Per-thread ids (tids) and the user-space resources (stack + trap-context
+//! page) each thread needs inside a `memory_set` shared via `CLONE_VM`,
+//! mirroring the established pid allocator.
+
+use super::TaskControlBlock;
+use crate::config::PAGE_SIZE;
+use crate::mm::MapPermission;
+use crate::sync::UPSafeCell;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use lazy_static::*;
+
+const THREAD_USER_STACK_SIZE: usize = PAGE_SIZE * 2;
+/// Base of the per-thread stack/trap-context region, laid out below the
+/// trampoline the same way the single-threaded `TRAP_CONTEXT` is.
+const THREAD_RES_BASE: usize = 0x1_0000_0000 - (THREAD_USER_STACK_SIZE + PAGE_SIZE) * 2;
+
+struct TidAllocator {
+    current: usize,
+    recycled: Vec<usize>,
+}
+
+impl TidAllocator {
+    fn new() -> Self {
+        Self {
+            current: 0,
+            recycled: Vec::new(),
+        }
+    }
+    fn alloc(&mut self) -> usize {
+        if let Some(tid) = self.recycled.pop() {
+            tid
+        } else {
+            self.current += 1;
+            self.current - 1
+        }
+    }
+    fn dealloc(&mut self, tid: usize) {
+        debug_assert!(!self.recycled.contains(&tid));
+        self.recycled.push(tid);
+    }
+}
+
+lazy_static! {
+    static ref TID_ALLOCATOR: UPSafeCell<TidAllocator> =
+        unsafe { UPSafeCell::new(TidAllocator::new()) };
+}
+
+/// An allocated tid, recycled back to `TID_ALLOCATOR` on drop.
+pub struct TidHandle(pub usize);
+
+impl Drop for TidHandle {
+    fn drop(&mut self) {
+        TID_ALLOCATOR.exclusive_access().dealloc(self.0);
+    }
+}
+
+fn ustack_bottom_from_tid(tid: usize) -> usize {
+    THREAD_RES_BASE + tid * (THREAD_USER_STACK_SIZE + PAGE_SIZE)
+}
+
+fn trap_cx_bottom_from_tid(tid: usize) -> usize {
+    ustack_bottom_from_tid(tid) + THREAD_USER_STACK_SIZE
+}
+
+/// The per-thread slice of a shared address space: a tid, a user stack, and
+/// a trap-context page, all carved out of the owning task's `memory_set`.
+pub struct TaskUserRes {
+    pub tid: TidHandle,
+    pub ustack_base: usize,
+    pub task: Weak<TaskControlBlock>,
+}
+
+impl TaskUserRes {
+    /// Allocate a tid and map its stack/trap-context pages into `task`'s
+    /// (shared) `memory_set`.
+    pub fn alloc(task: &Arc<TaskControlBlock>) -> Self {
+        let tid = TID_ALLOCATOR.exclusive_access().alloc();
+        let res = Self {
+            tid: TidHandle(tid),
+            ustack_base: ustack_bottom_from_tid(tid),
+            task: Arc::downgrade(task),
+        };
+        res.alloc_user_res();
+        res
+    }
+
+    fn alloc_user_res(&self) {
+        let task = self.task.upgrade().unwrap();
+        let mut inner = task.inner_exclusive_access();
+        let ustack_top = self.ustack_base + THREAD_USER_STACK_SIZE;
+        inner.memory_set.insert_framed_area_check(
+            self.ustack_base.into(),
+            ustack_top.into(),
+            MapPermission::R | MapPermission::W | MapPermission::U,
+        );
+        let trap_cx_bottom = self.trap_cx_user_va();
+        let trap_cx_top = trap_cx_bottom + PAGE_SIZE;
+        inner.memory_set.insert_framed_area_check(
+            trap_cx_bottom.into(),
+            trap_cx_top.into(),
+            MapPermission::R | MapPermission::W,
+        );
+    }
+
+    pub fn ustack_top(&self) -> usize {
+        self.ustack_base + THREAD_USER_STACK_SIZE
+    }
+
+    pub fn trap_cx_user_va(&self) -> usize {
+        trap_cx_bottom_from_tid(self.tid.0)
+    }
+
+    /// Unmap this thread's stack/trap-context pages. The tid goes back to
+    /// `TID_ALLOCATOR` when `self` (and with it the `TidHandle`) is dropped.
+    /// Called from `exit_current_and_run_next` when a thread (rather than
+    /// the last task in a process) exits.
+    pub fn dealloc_user_res(&self) {
+        let Some(task) = self.task.upgrade() else {
+            return;
+        };
+        let mut inner = task.inner_exclusive_access();
+        let ustack_top = self.ustack_base + THREAD_USER_STACK_SIZE;
+        inner
+            .memory_set
+            .move_frame_area_check(self.ustack_base.into(), ustack_top.into());
+        let trap_cx_bottom = self.trap_cx_user_va();
+        let trap_cx_top = trap_cx_bottom + PAGE_SIZE;
+        inner
+            .memory_set
+            .move_frame_area_check(trap_cx_bottom.into(), trap_cx_top.into());
+    }
+}
@@ -10,6 +10,13 @@ pub struct Info {
     pub start_time: usize,
     /// Priority of current task
     pub priority: Priority,
+    /// The scheduling discipline `TaskManager` should use for this task.
+    pub policy: SchedPolicy,
+    /// Real-time priority used to order FIFO/RR tasks against each other
+    /// (higher runs first); unused under `Stride`.
+    pub rt_priority: usize,
+    /// Milliseconds this task has run since its current RR quantum began.
+    pub rr_elapsed_ms: usize,
 }
 
 impl Info {
@@ -31,6 +38,23 @@ impl Info {
     pub fn during_time(&self) -> usize {
         get_time_us()/1000 - self.start_time
     }
+
+    /// Whether a `RoundRobin` task has used up its quantum and should be
+    /// preempted on this timer tick; always `false` under other policies.
+    pub fn rr_quantum_expired(&self) -> bool {
+        match self.policy {
+            SchedPolicy::RoundRobin { quantum_ms } => self.rr_elapsed_ms >= quantum_ms,
+            _ => false,
+        }
+    }
+
+    pub fn record_tick(&mut self, tick_ms: usize) {
+        self.rr_elapsed_ms += tick_ms;
+    }
+
+    pub fn reset_rr_quantum(&mut self) {
+        self.rr_elapsed_ms = 0;
+    }
 }
 
 impl Default for Info {
@@ -39,10 +63,67 @@ impl Default for Info {
             syscall_times: [0; MAX_SYSCALL_NUM],
             start_time: 0,
             priority: Default::default(),
+            policy: Default::default(),
+            rt_priority: 0,
+            rr_elapsed_ms: 0,
         }
     }
 }
 
+/// A task's scheduling discipline, selectable per task via
+/// `sys_sched_setscheduler`. Ready FIFO/RR tasks are always dispatched ahead
+/// of Stride tasks; a Stride task that is already running is not preempted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SchedPolicy {
+    /// Real-time, run-to-completion-or-yield, ordered by `rt_priority`.
+    Fifo,
+    /// Real-time, round-robin among equal `rt_priority` every `quantum_ms`.
+    RoundRobin { quantum_ms: usize },
+    /// Best-effort, ordered by stride-scheduling `pass`.
+    Stride,
+}
+
+impl Default for SchedPolicy {
+    fn default() -> Self {
+        SchedPolicy::Stride
+    }
+}
+
+/// The resource id accepted by `sys_getrlimit`/`sys_setrlimit` for the
+/// address-space size limit, matching Linux's `RLIMIT_AS`.
+pub const RLIMIT_AS: usize = 9;
+
+/// No limit; matches Linux's `RLIM_INFINITY`.
+pub const RLIM_INFINITY: u64 = u64::MAX;
+
+/// A POSIX `rlimit`: the soft (`cur`) and hard (`max`) bound for a resource.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RLimit64 {
+    pub cur: u64,
+    pub max: u64,
+}
+
+impl Default for RLimit64 {
+    fn default() -> Self {
+        Self {
+            cur: RLIM_INFINITY,
+            max: RLIM_INFINITY,
+        }
+    }
+}
+
+/// Accounting for one address space: how much of it `sys_mmap` has used,
+/// and its `RLIMIT_AS`. Every task sharing a `memory_set` (`CLONE_VM`
+/// siblings, threads) shares one of these too, via
+/// `TaskControlBlockInner::limits`, so the limit is enforced against the
+/// address space as a whole rather than double-counted per task.
+#[derive(Clone, Copy, Default)]
+pub struct AddressSpaceLimits {
+    pub mapped_bytes: usize,
+    pub rlimit_as: RLimit64,
+}
+
 #[derive(Clone, Copy)]
 pub struct Priority {
     pub(crate) pass: u64,
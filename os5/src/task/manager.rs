@@ -4,43 +4,98 @@
 //! Other CPU process monitoring functions are in Processor.
 
 
-use alloc::collections::VecDeque;
+use alloc::collections::{BinaryHeap, VecDeque};
 use super::TaskControlBlock;
+use crate::task::info::{Priority, SchedPolicy};
 use crate::sync::UPSafeCell;
 use alloc::sync::Arc;
+use core::cmp::Reverse;
 use lazy_static::*;
 use crate::task::current_task;
 
+/// A ready-queue entry ordered by the `Priority` snapshot taken when the task
+/// was enqueued. The task only updates its own pass when it is dispatched
+/// (see `TaskManager::fetch`), so the snapshot stays valid for the whole time
+/// the task sits in the heap.
+struct StrideEntry {
+    priority: Priority,
+    task: Arc<TaskControlBlock>,
+}
+
+impl PartialEq for StrideEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for StrideEntry {}
+
+impl PartialOrd for StrideEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.priority.partial_cmp(&other.priority)
+    }
+}
+
+impl Ord for StrideEntry {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
 pub struct TaskManager {
-    // ready_queue: VecDeque<Arc<TaskControlBlock>>,
-    task_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// Real-time (`Fifo`/`RoundRobin`) tasks, kept sorted by descending
+    /// `rt_priority`; ties are FIFO-ordered so `RoundRobin` tasks rotate.
+    rt_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// Best-effort (`Stride`) tasks: a min-heap over `(round, pass)`,
+    /// implemented as a max-heap of `Reverse`.
+    stride_queue: BinaryHeap<Reverse<StrideEntry>>,
 }
 
-// YOUR JOB: FIFO->Stride
-/// A simple FIFO scheduler.
+/// A scheduler that mixes scheduling classes: real-time `Fifo`/`RoundRobin`
+/// tasks are always dispatched ahead of best-effort `Stride` tasks, and the
+/// `Stride` tasks fall back to pass-based stride scheduling among
+/// themselves.
 impl TaskManager {
     pub fn new() -> Self {
         Self {
-            task_queue: VecDeque::new(),
+            rt_queue: VecDeque::new(),
+            stride_queue: BinaryHeap::new(),
         }
     }
     /// Add process back to ready queue
     pub fn add(&mut self, task: Arc<TaskControlBlock>) {
-        if task.pass() == 0 {
-            self.task_queue.push_front(task);
-        }else {
-            self.task_queue.push_back(task);
+        let info = task.inner_exclusive_access().info;
+        match info.policy {
+            SchedPolicy::Fifo | SchedPolicy::RoundRobin { .. } => {
+                // Insert after every task with priority >= this one's, so
+                // equal-priority RoundRobin tasks rotate to the back.
+                let pos = self
+                    .rt_queue
+                    .iter()
+                    .position(|t| t.inner_exclusive_access().info.rt_priority < info.rt_priority);
+                match pos {
+                    Some(idx) => self.rt_queue.insert(idx, task),
+                    None => self.rt_queue.push_back(task),
+                }
+            }
+            SchedPolicy::Stride => {
+                self.stride_queue.push(Reverse(StrideEntry {
+                    priority: info.priority,
+                    task,
+                }));
+            }
         }
     }
-    /// Take a process out of the ready queue
+    /// Take the highest-class runnable task out of the ready queue: a
+    /// real-time task if any is ready, else the `Stride` task with the
+    /// smallest pass.
     pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
-        let task = self.task_queue.iter().enumerate().min_by_key(|(_id, task)| {
-            let inner_ref = (*task).inner_exclusive_access();
-            inner_ref.info.priority
-        });
-        let task_idx = task.unwrap().0;
-        // info!("Task {:?} is fetched!", task_idx);
-        self.task_queue.remove(task_idx)
+        if let Some(task) = self.rt_queue.pop_front() {
+            return Some(task);
+        }
+        let Reverse(StrideEntry { task, .. }) = self.stride_queue.pop()?;
+        task.inner_exclusive_access().info.priority.update_pass();
+        Some(task)
     }
 }
 
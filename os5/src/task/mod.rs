@@ -0,0 +1,39 @@
+//! Task management: process/thread control blocks, the ready-queue
+//! scheduler, and the state (scheduling, vfork, ptrace, seccomp, thread
+//! resources) built on top of them.
+
+mod clone;
+pub mod context;
+pub mod id;
+pub mod info;
+pub mod manager;
+mod pid;
+mod pid_table;
+pub mod processor;
+pub mod ptrace;
+pub mod seccomp;
+mod task;
+mod thread;
+pub mod vfork;
+
+use crate::loader::get_app_data_by_name;
+use alloc::sync::Arc;
+use lazy_static::*;
+
+pub use manager::add_task;
+pub use processor::{
+    current_task, current_trap_cx, current_user_token, exit_current_and_run_next, run_tasks,
+    suspend_current_and_run_next, take_current_task, timer_tick,
+};
+pub use task::{TaskControlBlock, TaskControlBlockInner, TaskStatus};
+
+lazy_static! {
+    /// The first user-space process, whose children are re-parented
+    /// zombies reaped here (not yet wired up by this series).
+    pub static ref INITPROC: Arc<TaskControlBlock> =
+        Arc::new(TaskControlBlock::new(get_app_data_by_name("initproc").unwrap()));
+}
+
+pub fn add_initproc() {
+    add_task(INITPROC.clone());
+}
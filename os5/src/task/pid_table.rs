@@ -0,0 +1,59 @@
+//! A small pid-keyed side table for task state that doesn't belong on every
+//! `TaskControlBlockInner`, because most tasks never touch it: vfork waits,
+//! ptrace state, and seccomp filters are each one of these, and had each
+//! grown their own hand-rolled `UPSafeCell<BTreeMap<..>>` before this.
+
+use crate::sync::UPSafeCell;
+use alloc::collections::BTreeMap;
+
+pub struct PidTable<T> {
+    inner: UPSafeCell<BTreeMap<usize, T>>,
+}
+
+impl<T> PidTable<T> {
+    pub fn new() -> Self {
+        Self {
+            inner: unsafe { UPSafeCell::new(BTreeMap::new()) },
+        }
+    }
+
+    pub fn insert(&self, pid: usize, value: T) {
+        self.inner.exclusive_access().insert(pid, value);
+    }
+
+    pub fn remove(&self, pid: usize) {
+        self.inner.exclusive_access().remove(&pid);
+    }
+}
+
+impl<T: Clone> PidTable<T> {
+    pub fn get(&self, pid: usize) -> Option<T> {
+        self.inner.exclusive_access().get(&pid).cloned()
+    }
+}
+
+impl<T> PidTable<T> {
+    /// Like `get`, but runs `f` against the entry by reference instead of
+    /// cloning it, for callers on a hot path (e.g. a per-syscall lookup)
+    /// where `T` is too big to clone on every call.
+    pub fn with<R>(&self, pid: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.inner.exclusive_access().get(&pid).map(f)
+    }
+}
+
+impl<T: Default> PidTable<T> {
+    /// Run `f` against `pid`'s entry, creating a default one first if this
+    /// is its first use.
+    pub fn update<R>(&self, pid: usize, f: impl FnOnce(&mut T) -> R) -> R {
+        f(self.inner.exclusive_access().entry(pid).or_default())
+    }
+}
+
+impl<T> PidTable<T> {
+    /// Like `update`, but a no-op (returning `None`) if `pid` has no entry
+    /// yet, for callers where creating one would just leak state nothing
+    /// will ever clean up.
+    pub fn update_if_present<R>(&self, pid: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.inner.exclusive_access().get_mut(&pid).map(f)
+    }
+}
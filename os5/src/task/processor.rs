@@ -0,0 +1,157 @@
+//! Track the currently-running task on this core and drive the scheduler.
+
+use super::context::TaskContext;
+use super::manager::{add_task, fetch_task};
+use super::task::{TaskControlBlock, TaskStatus};
+use crate::sync::UPSafeCell;
+use crate::trap::TrapContext;
+use alloc::sync::Arc;
+use lazy_static::*;
+
+extern "C" {
+    fn __switch(current_task_cx_ptr: *mut TaskContext, next_task_cx_ptr: *mut TaskContext);
+}
+
+pub struct Processor {
+    current: Option<Arc<TaskControlBlock>>,
+    idle_task_cx: TaskContext,
+}
+
+impl Processor {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            idle_task_cx: TaskContext::zero_init(),
+        }
+    }
+    fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext {
+        &mut self.idle_task_cx as *mut _
+    }
+    pub fn take_current(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.current.take()
+    }
+    pub fn current(&self) -> Option<Arc<TaskControlBlock>> {
+        self.current.as_ref().map(Arc::clone)
+    }
+}
+
+lazy_static! {
+    pub static ref PROCESSOR: UPSafeCell<Processor> = unsafe { UPSafeCell::new(Processor::new()) };
+}
+
+pub fn take_current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSOR.exclusive_access().take_current()
+}
+
+pub fn current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSOR.exclusive_access().current()
+}
+
+pub fn current_user_token() -> usize {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .memory_set
+        .token()
+}
+
+pub fn current_trap_cx() -> &'static mut TrapContext {
+    current_task().unwrap().inner_exclusive_access().get_trap_cx()
+}
+
+/// Meant to be called on every timer interrupt (from `crate::trap::trap_handler`)
+/// to charge the current task's `RoundRobin` quantum, preempting it once the
+/// quantum is exhausted. A no-op for `Fifo`/`Stride` tasks, whose
+/// `rr_quantum_expired` is always `false`.
+///
+/// `trap_handler` isn't part of this module tree, so nothing calls this yet
+/// — wiring the actual timer-interrupt call site belongs there, not here.
+pub fn timer_tick(tick_ms: usize) {
+    let Some(task) = current_task() else {
+        return;
+    };
+    let expired = {
+        let mut inner = task.inner_exclusive_access();
+        inner.info.record_tick(tick_ms);
+        inner.info.rr_quantum_expired()
+    };
+    if expired {
+        task.inner_exclusive_access().info.reset_rr_quantum();
+        drop(task);
+        suspend_current_and_run_next();
+    }
+}
+
+/// The idle loop: dispatch the next runnable task, switch to it, and come
+/// back here when it next suspends.
+pub fn run_tasks() {
+    loop {
+        let mut processor = PROCESSOR.exclusive_access();
+        let Some(task) = fetch_task() else {
+            continue;
+        };
+        // A task parked by `PTRACE_ATTACH` stays out of rotation until
+        // `PTRACE_CONT` re-adds it (see `syscall::ptrace::sys_ptrace`); on
+        // this single-core kernel the attacher can only be the currently
+        // running task, so a stopped tracee is always sitting in the ready
+        // queue here rather than actually running, making this spot, right
+        // after `fetch_task`, the one place that needs to check.
+        if super::ptrace::is_stopped(task.getpid()) {
+            continue;
+        }
+        let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+        let next_task_cx_ptr = {
+            let mut task_inner = task.inner_exclusive_access();
+            task_inner.task_status = TaskStatus::Running;
+            &mut task_inner.task_cx as *mut TaskContext
+        };
+        processor.current = Some(task);
+        drop(processor);
+        unsafe {
+            __switch(idle_task_cx_ptr, next_task_cx_ptr);
+        }
+    }
+}
+
+/// Switch from `switched_task_cx_ptr` back to the idle loop in `run_tasks`.
+pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
+    let mut processor = PROCESSOR.exclusive_access();
+    let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+    drop(processor);
+    unsafe {
+        __switch(switched_task_cx_ptr, idle_task_cx_ptr);
+    }
+}
+
+/// Voluntarily give up the CPU, going back into the ready queue (see
+/// `TaskManager::add` for where it is placed).
+pub fn suspend_current_and_run_next() {
+    let task = take_current_task().unwrap();
+    let task_cx_ptr = {
+        let mut inner = task.inner_exclusive_access();
+        inner.task_status = TaskStatus::Ready;
+        &mut inner.task_cx as *mut TaskContext
+    };
+    add_task(task);
+    schedule(task_cx_ptr);
+}
+
+/// Mark the current task a zombie, release the thread-local resources it
+/// holds (tid, user stack, trap-context page), and switch away for good.
+pub fn exit_current_and_run_next(exit_code: i32) {
+    let task = take_current_task().unwrap();
+    let res = {
+        let mut inner = task.inner_exclusive_access();
+        inner.task_status = TaskStatus::Zombie;
+        inner.exit_code = exit_code;
+        inner.task_res.take()
+    };
+    // `dealloc_user_res` borrows this same task's inner, so it runs only
+    // after the borrow above has been released.
+    if let Some(res) = res {
+        res.dealloc_user_res();
+    }
+    drop(task);
+    let mut unused = TaskContext::zero_init();
+    schedule(&mut unused as *mut TaskContext);
+}
@@ -0,0 +1,58 @@
+//! Per-task ptrace state, stored in a side table (mirroring `vfork`'s
+//! approach) rather than growing `TaskControlBlockInner` for a feature most
+//! tasks never use.
+
+use super::pid_table::PidTable;
+use super::TaskControlBlock;
+use alloc::sync::{Arc, Weak};
+use lazy_static::*;
+
+/// `request` values accepted by `sys_ptrace`.
+pub const PTRACE_TRACEME: usize = 0;
+pub const PTRACE_PEEKDATA: usize = 1;
+pub const PTRACE_POKEDATA: usize = 2;
+pub const PTRACE_CONT: usize = 3;
+pub const PTRACE_ATTACH: usize = 4;
+pub const PTRACE_GETREGS: usize = 5;
+pub const PTRACE_SETREGS: usize = 6;
+
+#[derive(Default, Clone)]
+struct PtraceState {
+    /// Set by `PTRACE_TRACEME`: stop and auto-attach to the parent on exec.
+    traceme: bool,
+    stopped: bool,
+    tracer: Option<Weak<TaskControlBlock>>,
+}
+
+lazy_static! {
+    static ref PTRACE_STATE: PidTable<PtraceState> = PidTable::new();
+}
+
+pub fn mark_traceme(pid: usize) {
+    PTRACE_STATE.update(pid, |s| s.traceme = true);
+}
+
+pub fn wants_traceme(pid: usize) -> bool {
+    PTRACE_STATE.get(pid).map(|s| s.traceme).unwrap_or(false)
+}
+
+/// `tracer` becomes `pid`'s tracer and `pid` stops until `PTRACE_CONT`.
+pub fn attach(tracer: &Arc<TaskControlBlock>, pid: usize) {
+    PTRACE_STATE.update(pid, |s| {
+        s.stopped = true;
+        s.tracer = Some(Arc::downgrade(tracer));
+    });
+}
+
+pub fn cont(pid: usize) {
+    PTRACE_STATE.update_if_present(pid, |s| s.stopped = false);
+}
+
+pub fn is_stopped(pid: usize) -> bool {
+    PTRACE_STATE.get(pid).map(|s| s.stopped).unwrap_or(false)
+}
+
+/// The registered tracer for `pid`, if it is still alive.
+pub fn tracer_of(pid: usize) -> Option<Arc<TaskControlBlock>> {
+    PTRACE_STATE.get(pid)?.tracer?.upgrade()
+}
@@ -0,0 +1,68 @@
+//! Per-task syscall filtering, consulted by the syscall dispatcher right
+//! before `Info::record_syscall` (the same hook that already counts every
+//! syscall) so counting doubles as a sandboxing choke point. A syscall
+//! outside a task's policy always kills the task outright (there is no
+//! `-EPERM`-and-continue action); see `SECCOMP_KILL_EXIT_CODE`.
+
+use super::pid_table::PidTable;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+pub const SECCOMP_MODE_STRICT: usize = 1;
+pub const SECCOMP_MODE_FILTER: usize = 2;
+
+/// Exit code used when either `STRICT` or `FILTER` mode kills the task for
+/// an out-of-policy syscall.
+pub const SECCOMP_KILL_EXIT_CODE: i32 = -159;
+
+const SYSCALL_READ: usize = 63;
+const SYSCALL_WRITE: usize = 64;
+const SYSCALL_EXIT: usize = 93;
+const SYSCALL_YIELD: usize = 124;
+const STRICT_ALLOWLIST: [usize; 4] = [SYSCALL_READ, SYSCALL_WRITE, SYSCALL_EXIT, SYSCALL_YIELD];
+
+#[derive(Clone)]
+enum Filter {
+    Strict,
+    Bitmap(Vec<bool>),
+}
+
+#[derive(Clone)]
+struct SeccompState {
+    filter: Filter,
+}
+
+lazy_static! {
+    static ref SECCOMP: PidTable<SeccompState> = PidTable::new();
+}
+
+pub fn set_strict(pid: usize) {
+    SECCOMP.insert(pid, SeccompState { filter: Filter::Strict });
+}
+
+pub fn set_filter(pid: usize, bitmap: Vec<bool>) {
+    SECCOMP.insert(pid, SeccompState { filter: Filter::Bitmap(bitmap) });
+}
+
+/// Copy `parent_pid`'s filter (if any) onto `child_pid`, so `fork`/`clone`
+/// children can't escape a sandbox by spawning.
+pub fn inherit(parent_pid: usize, child_pid: usize) {
+    if let Some(state) = SECCOMP.get(parent_pid) {
+        SECCOMP.insert(child_pid, state);
+    }
+}
+
+/// Whether `pid` may make `syscall_id`.
+///
+/// Consulted on every syscall, so this reads `SECCOMP` through `with`
+/// instead of `get`: a filtered task's `Filter::Bitmap` is `MAX_SYSCALL_NUM`
+/// entries, and cloning that on every syscall would be a fresh allocation
+/// per call for no reason.
+pub fn is_allowed(pid: usize, syscall_id: usize) -> bool {
+    SECCOMP
+        .with(pid, |state| match &state.filter {
+            Filter::Strict => STRICT_ALLOWLIST.contains(&syscall_id),
+            Filter::Bitmap(bitmap) => bitmap.get(syscall_id).copied().unwrap_or(false),
+        })
+        .unwrap_or(true)
+}
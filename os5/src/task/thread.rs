@@ -0,0 +1,79 @@
+//! Thread creation and join, built on the shared-address-space fork from
+//! `CLONE_VM` and the per-thread resources in `TaskUserRes`.
+
+use super::id::TaskUserRes;
+use super::TaskControlBlock;
+use crate::mm::VirtAddr;
+use alloc::sync::Arc;
+
+impl TaskControlBlock {
+    /// Spawn a new thread sharing `self`'s address space, entering at
+    /// `entry` with `arg` in `a0`.
+    pub fn new_thread(self: &Arc<Self>, entry: usize, arg: usize) -> Arc<Self> {
+        let new_task = self.fork_shared_vm();
+        let res = TaskUserRes::alloc(&new_task);
+        {
+            let mut inner = new_task.inner_exclusive_access();
+            // `fork_shared_vm` left `trap_cx_ppn` pointing at the private,
+            // pid-indexed trap-context page it mapped and seeded with the
+            // parent's registers. `TaskUserRes::alloc` has since mapped this
+            // thread its own tid-indexed page, so retarget `trap_cx_ppn`
+            // there; that page's contents are not copied over, and only the
+            // fields set below are written to it.
+            inner.trap_cx_ppn = inner
+                .memory_set
+                .translate(VirtAddr::from(res.trap_cx_user_va()).into())
+                .unwrap()
+                .ppn();
+            // That page superseded the pid-indexed one `fork_shared_vm`
+            // mapped for us; drop it so it doesn't sit around unused for
+            // the rest of this thread's life.
+            super::clone::unmap_private_trap_cx(&mut inner.memory_set, new_task.getpid());
+            let ustack_top = res.ustack_top();
+            let kernel_sp = new_task.kernel_stack.get_top();
+            let trap_cx = inner.get_trap_cx();
+            trap_cx.sepc = entry;
+            trap_cx.x[10] = arg;
+            trap_cx.x[2] = ustack_top;
+            trap_cx.kernel_sp = kernel_sp;
+            inner.task_res = Some(res);
+        }
+        new_task
+    }
+
+    /// This task's tid, or 0 for the process's original (main) task, which
+    /// has no `TaskUserRes`.
+    pub fn gettid(&self) -> usize {
+        self.inner_exclusive_access()
+            .task_res
+            .as_ref()
+            .map(|res| res.tid.0)
+            .unwrap_or(0)
+    }
+
+    /// Wait for the sibling thread `tid` to exit, returning its exit code,
+    /// or -1/-2 as `sys_waitpid` does for "no such thread"/"still running".
+    pub fn wait_thread(self: &Arc<Self>, tid: usize) -> isize {
+        let inner = self.inner_exclusive_access();
+        // `children` also holds ordinary (non-thread) children, whose
+        // `gettid()` defaults to 0; skip those so they can't be mistaken
+        // for a real thread with that same default tid.
+        let child = inner
+            .children
+            .iter()
+            .find(|c| c.inner_exclusive_access().task_res.is_some() && c.gettid() == tid)
+            .cloned();
+        drop(inner);
+        match child {
+            None => -1,
+            Some(child) => {
+                let child_inner = child.inner_exclusive_access();
+                if child_inner.is_zombie() {
+                    child_inner.exit_code as isize
+                } else {
+                    -2
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Bookkeeping for `CLONE_VFORK` waits.
+//!
+//! The parent blocks until the child either execs or exits. Exit is already
+//! observable through the child's own `TaskControlBlock` (`is_zombie`), but
+//! exec is not, so we track a "done" flag per pid here instead of growing
+//! `TaskControlBlockInner` just for this one-shot signal.
+
+use super::pid_table::PidTable;
+use lazy_static::*;
+
+lazy_static! {
+    static ref VFORK_DONE: PidTable<bool> = PidTable::new();
+}
+
+/// Register `pid` as a vfork child the caller is about to wait on.
+pub fn vfork_wait_begin(pid: usize) {
+    VFORK_DONE.insert(pid, false);
+}
+
+/// Whether `pid`'s vfork child has execed since `vfork_wait_begin`.
+pub fn vfork_is_done(pid: usize) -> bool {
+    VFORK_DONE.get(pid).unwrap_or(true)
+}
+
+/// Mark `pid` as having execed, waking up any caller waiting on it.
+pub fn vfork_mark_done(pid: usize) {
+    VFORK_DONE.update_if_present(pid, |done| *done = true);
+}
+
+/// Stop tracking `pid`'s vfork wait once the caller has resumed.
+pub fn vfork_wait_end(pid: usize) {
+    VFORK_DONE.remove(pid);
+}